@@ -3,15 +3,18 @@
 use std::iter;
 use std::borrow::Borrow;
 
-use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::ristretto;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
 
 // XXX upstream into dalek
 use scalar;
 
 use proof_transcript::ProofTranscript;
 
+use merlin::Transcript;
+
 use util;
 
 use range_proof::inner_product;
@@ -19,10 +22,59 @@ use range_proof::make_generators;
 
 use sha2::Sha256;
 
+/// A transcript that the inner-product protocol commits `L`/`R` to and
+/// draws round challenges from. Both the crate's own
+/// `ProofTranscript` and `MerlinTranscript` (a STROBE-based Merlin
+/// transcript) implement this, so `Proof::create`/`verify` compose
+/// under either, and under any parent protocol built on the same
+/// transcript type.
+pub trait ProofTranscriptProtocol {
+    /// Commit a message to the transcript under `label`.
+    fn commit_bytes(&mut self, label: &'static [u8], message: &[u8]);
+    /// Draw a challenge scalar from the transcript under `label`.
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+}
+
+// `ProofTranscript` has no notion of labels, so this impl ignores
+// `label`; only `MerlinTranscript` domain-separates by label.
+impl ProofTranscriptProtocol for ProofTranscript {
+    fn commit_bytes(&mut self, _label: &'static [u8], message: &[u8]) {
+        self.commit(message);
+    }
+
+    fn challenge_scalar(&mut self, _label: &'static [u8]) -> Scalar {
+        ProofTranscript::challenge_scalar(self)
+    }
+}
+
+/// A Merlin (STROBE-based) transcript, with labeled `append_message`/
+/// `challenge_bytes` operations for the `L`/`R` commitments and the
+/// round challenge, so two independent verifiers deriving the same
+/// challenge sequence over Merlin are guaranteed to agree byte-for-byte.
+pub struct MerlinTranscript(Transcript);
+
+impl MerlinTranscript {
+    pub fn new(label: &'static [u8]) -> MerlinTranscript {
+        MerlinTranscript(Transcript::new(label))
+    }
+}
+
+impl ProofTranscriptProtocol for MerlinTranscript {
+    fn commit_bytes(&mut self, label: &'static [u8], message: &[u8]) {
+        self.0.append_message(label, message);
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        let mut bytes = [0u8; 64];
+        self.0.challenge_bytes(label, &mut bytes);
+        Scalar::from_bytes_mod_order_wide(&bytes)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Proof {
-    L_vec: Vec<RistrettoPoint>,
-    R_vec: Vec<RistrettoPoint>,
+    L_vec: Vec<CompressedRistretto>,
+    R_vec: Vec<CompressedRistretto>,
     a: Scalar,
     b: Scalar,
 }
@@ -33,22 +85,149 @@ impl Proof {
     /// The proof is created with respect to the bases G, Hprime,
     /// where Hprime[i] = H[i] * Hprime_factors[i].
     ///
+    /// `G_vec`, `H_vec`, `a_vec` and `b_vec` need not have a
+    /// power-of-two length: they are padded up to the next power of
+    /// two internally, with zeros in `a_vec`/`b_vec` and a fixed,
+    /// deterministic set of extra generators (see
+    /// `Proof::padding_generators`) in `G_vec`/`H_vec`. The padding
+    /// entries don't contribute to the inner product, so `verify`
+    /// only needs the true (unpadded) `G_vec`/`H_vec` to reconstruct
+    /// the same padding and check the proof.
+    ///
     /// The `verifier` is passed in as a parameter so that the
     /// challenges depend on the *entire* transcript (including parent
     /// protocols).
-    pub fn create<I>(
-        verifier: &mut ProofTranscript,
+    pub fn create<T, I>(
+        verifier: &mut T,
+        Q: &RistrettoPoint,
+        Hprime_factors: I,
+        G_vec: Vec<RistrettoPoint>,
+        H_vec: Vec<RistrettoPoint>,
+        a_vec: Vec<Scalar>,
+        b_vec: Vec<Scalar>,
+    ) -> Proof
+    where
+        T: ProofTranscriptProtocol,
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+    {
+        let (proof, _) = Proof::reduce::<T, I, iter::Empty<Scalar>>(
+            verifier,
+            Q,
+            Hprime_factors,
+            G_vec,
+            H_vec,
+            a_vec,
+            b_vec,
+            None,
+        );
+        proof
+    }
+
+    /// Create a hiding (zero-knowledge) inner-product proof.
+    ///
+    /// This is the same reduction as `create`, except every round's
+    /// `L`, `R` commitment is blinded with its own `l_blind`/`r_blind`
+    /// pair against the blinding base `H_blind`, and `P` is assumed to
+    /// already carry an `initial_blind * H_blind` term. The returned
+    /// scalar is the aggregate blind, updated each round as
+    /// `blind' = blind + x^2 * l_blind + x^-2 * r_blind`, which the
+    /// verifier needs to reconstruct the blinded `P` in
+    /// `verify_blinded`.
+    ///
+    /// This lets the inner-product argument be used as a standalone
+    /// hiding commitment-opening proof, rather than only as a
+    /// subprotocol nested inside a hiding range proof. `G_vec`/`H_vec`/
+    /// `a_vec`/`b_vec` are padded the same way as in `create`.
+    pub fn create_blinded<T, I, J>(
+        verifier: &mut T,
+        Q: &RistrettoPoint,
+        H_blind: &RistrettoPoint,
+        Hprime_factors: I,
+        G_vec: Vec<RistrettoPoint>,
+        H_vec: Vec<RistrettoPoint>,
+        a_vec: Vec<Scalar>,
+        b_vec: Vec<Scalar>,
+        initial_blind: Scalar,
+        l_blinds: J,
+        r_blinds: J,
+    ) -> (Proof, Scalar)
+    where
+        T: ProofTranscriptProtocol,
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator<Item = Scalar>,
+    {
+        let (proof, blind) = Proof::reduce(
+            verifier,
+            Q,
+            Hprime_factors,
+            G_vec,
+            H_vec,
+            a_vec,
+            b_vec,
+            Some((*H_blind, initial_blind, l_blinds, r_blinds)),
+        );
+        (proof, blind.expect("reduce returns Some(blind) whenever blind params are Some"))
+    }
+
+    /// Shared inner-product reduction behind `create`/`create_blinded`.
+    ///
+    /// When `blind` is `None`, every round's `l_blind`/`r_blind` is
+    /// treated as zero and `H_blind` is unused, which is exactly
+    /// `create`'s unblinded reduction; the returned blind is `None` in
+    /// that case. When `blind` is `Some((H_blind, initial_blind,
+    /// l_blinds, r_blinds))`, this is `create_blinded`'s hiding
+    /// reduction, and the returned blind is `Some(final_blind)`.
+    fn reduce<T, I, J>(
+        verifier: &mut T,
         Q: &RistrettoPoint,
         Hprime_factors: I,
         mut G_vec: Vec<RistrettoPoint>,
         mut H_vec: Vec<RistrettoPoint>,
         mut a_vec: Vec<Scalar>,
         mut b_vec: Vec<Scalar>,
-    ) -> Proof
+        blind: Option<(RistrettoPoint, Scalar, J, J)>,
+    ) -> (Proof, Option<Scalar>)
     where
+        T: ProofTranscriptProtocol,
         I: IntoIterator,
         I::Item: Borrow<Scalar>,
+        J: IntoIterator<Item = Scalar>,
     {
+        let true_n = G_vec.len();
+        assert_eq!(H_vec.len(), true_n);
+        assert_eq!(a_vec.len(), true_n);
+        assert_eq!(b_vec.len(), true_n);
+
+        let padded_n = true_n.next_power_of_two();
+        // `Hprime_factors` may be an infinite iterator (e.g. `util::exp_iter`),
+        // so it must be bounded to the true length before it's collected.
+        let mut Hprime_factors: Vec<Scalar> = Hprime_factors
+            .into_iter()
+            .map(|h| *h.borrow())
+            .take(true_n)
+            .collect();
+        if padded_n != true_n {
+            let (G_pad, H_pad) = Proof::padding_generators(true_n, padded_n);
+            G_vec.extend(G_pad);
+            H_vec.extend(H_pad);
+            a_vec.resize(padded_n, Scalar::zero());
+            b_vec.resize(padded_n, Scalar::zero());
+            Hprime_factors.resize(padded_n, Scalar::from_u64(1));
+        }
+
+        let is_blinded = blind.is_some();
+        let (H_blind, mut running_blind, mut l_blinds, mut r_blinds) = match blind {
+            Some((H_blind, initial_blind, l_blinds, r_blinds)) => (
+                H_blind,
+                initial_blind,
+                Some(l_blinds.into_iter()),
+                Some(r_blinds.into_iter()),
+            ),
+            None => (RistrettoPoint::identity(), Scalar::zero(), None, None),
+        };
+
         // Create slices G, H, a, b backed by their respective
         // vectors.  This lets us reslice as we compress the lengths
         // of the vectors in the main loop below.
@@ -85,25 +264,51 @@ impl Proof {
             let c_L = inner_product(&a_L, &b_R);
             let c_R = inner_product(&a_R, &b_L);
 
+            let l_blind = l_blinds
+                .as_mut()
+                .map(|it| it.next().expect("not enough l_blinds for lg_n rounds"))
+                .unwrap_or(Scalar::zero());
+            let r_blind = r_blinds
+                .as_mut()
+                .map(|it| it.next().expect("not enough r_blinds for lg_n rounds"))
+                .unwrap_or(Scalar::zero());
+
             let L = ristretto::vartime::multiscalar_mult(
-                a_L.iter().chain(b_R.iter()).chain(iter::once(&c_L)),
-                G_R.iter().chain(H_L.iter()).chain(iter::once(Q)),
+                a_L.iter()
+                    .chain(b_R.iter())
+                    .chain(iter::once(&c_L))
+                    .chain(iter::once(&l_blind)),
+                G_R.iter()
+                    .chain(H_L.iter())
+                    .chain(iter::once(Q))
+                    .chain(iter::once(&H_blind)),
             );
 
             let R = ristretto::vartime::multiscalar_mult(
-                a_R.iter().chain(b_L.iter()).chain(iter::once(&c_R)),
-                G_L.iter().chain(H_R.iter()).chain(iter::once(Q)),
+                a_R.iter()
+                    .chain(b_L.iter())
+                    .chain(iter::once(&c_R))
+                    .chain(iter::once(&r_blind)),
+                G_L.iter()
+                    .chain(H_R.iter())
+                    .chain(iter::once(Q))
+                    .chain(iter::once(&H_blind)),
             );
 
+            let L = L.compress();
+            let R = R.compress();
+
+            verifier.commit_bytes(b"L", L.as_bytes());
+            verifier.commit_bytes(b"R", R.as_bytes());
+
             L_vec.push(L);
             R_vec.push(R);
 
-            verifier.commit(L.compress().as_bytes());
-            verifier.commit(R.compress().as_bytes());
-
-            let x = verifier.challenge_scalar();
+            let x = verifier.challenge_scalar(b"x");
             let x_inv = x.invert();
 
+            running_blind = running_blind + x * x * l_blind + x_inv * x_inv * r_blind;
+
             for i in 0..n {
                 a_L[i] = a_L[i] * x + x_inv * a_R[i];
                 b_L[i] = b_L[i] * x_inv + x * b_R[i];
@@ -117,24 +322,87 @@ impl Proof {
             H = H_L;
         }
 
-        return Proof {
+        let proof = Proof {
             L_vec: L_vec,
             R_vec: R_vec,
             a: a[0],
             b: b[0],
         };
+
+        let blind = if is_blinded { Some(running_blind) } else { None };
+
+        (proof, blind)
+    }
+
+    pub fn verify<T, I>(
+        &self,
+        verifier: &mut T,
+        Hprime_factors: I,
+        P: &RistrettoPoint,
+        Q: &RistrettoPoint,
+        G_vec: &Vec<RistrettoPoint>,
+        H_vec: &Vec<RistrettoPoint>,
+    ) -> Result<(), ()>
+    where
+        T: ProofTranscriptProtocol,
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+    {
+        self.check(verifier, Hprime_factors, P, Q, G_vec, H_vec, None)
+    }
+
+    /// Verifies a hiding proof produced by `create_blinded`.
+    ///
+    /// This is identical to `verify`, except the caller also supplies
+    /// the blinding base `H_blind` and the aggregate `blind` scalar
+    /// returned by `create_blinded`, and the reconstructed `P` has a
+    /// `blind * H_blind` term added before comparing against the
+    /// commitment.
+    pub fn verify_blinded<T, I>(
+        &self,
+        verifier: &mut T,
+        Hprime_factors: I,
+        P: &RistrettoPoint,
+        Q: &RistrettoPoint,
+        H_blind: &RistrettoPoint,
+        blind: &Scalar,
+        G_vec: &Vec<RistrettoPoint>,
+        H_vec: &Vec<RistrettoPoint>,
+    ) -> Result<(), ()>
+    where
+        T: ProofTranscriptProtocol,
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+    {
+        self.check(
+            verifier,
+            Hprime_factors,
+            P,
+            Q,
+            G_vec,
+            H_vec,
+            Some((*H_blind, *blind)),
+        )
     }
 
-    pub fn verify<I>(
+    /// Shared verification behind `verify`/`verify_blinded`.
+    ///
+    /// When `blind` is `None` this is exactly `verify`'s unblinded
+    /// check; when it's `Some((H_blind, blind))`, a `blind * H_blind`
+    /// term is folded into the reconstructed `P` before the
+    /// comparison, as `verify_blinded` requires.
+    fn check<T, I>(
         &self,
-        verifier: &mut ProofTranscript,
+        verifier: &mut T,
         Hprime_factors: I,
         P: &RistrettoPoint,
         Q: &RistrettoPoint,
         G_vec: &Vec<RistrettoPoint>,
         H_vec: &Vec<RistrettoPoint>,
+        blind: Option<(RistrettoPoint, Scalar)>,
     ) -> Result<(), ()>
     where
+        T: ProofTranscriptProtocol,
         I: IntoIterator,
         I::Item: Borrow<Scalar>,
     {
@@ -142,14 +410,41 @@ impl Proof {
         let lg_n = self.L_vec.len();
         let n = 1 << lg_n;
 
-        // XXX figure out how ser/deser works for Proofs
-        // maybe avoid this compression
+        // `G_vec`/`H_vec` are the caller's true (possibly non-power-of-
+        // two) bases; pad them out to `n` with the same fixed, extra
+        // generators `create` used, so the padding is consistent on
+        // both sides without needing to record the true length in the
+        // proof itself.
+        let true_n = G_vec.len();
+        assert_eq!(H_vec.len(), true_n);
+        let mut G_vec = G_vec.clone();
+        let mut H_vec = H_vec.clone();
+        // `Hprime_factors` may be an infinite iterator (e.g. `util::exp_iter`),
+        // so it must be bounded to the true length before it's collected.
+        let mut Hprime_factors: Vec<Scalar> = Hprime_factors
+            .into_iter()
+            .map(|h| *h.borrow())
+            .take(true_n)
+            .collect();
+        if n != true_n {
+            let (G_pad, H_pad) = Proof::padding_generators(true_n, n);
+            G_vec.extend(G_pad);
+            H_vec.extend(H_pad);
+            Hprime_factors.resize(n, Scalar::from_u64(1));
+        }
+        let G_vec = &G_vec;
+        let H_vec = &H_vec;
+
+        // L_vec/R_vec are stored compressed (see `Proof::to_bytes`), so
+        // committing them to the transcript doesn't need to re-compress
+        // anything; we only decompress them further down, when they're
+        // needed as points in the final multiscalar multiplication.
         let mut challenges = Vec::with_capacity(lg_n);
         for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
-            verifier.commit(L.compress().as_bytes());
-            verifier.commit(R.compress().as_bytes());
+            verifier.commit_bytes(b"L", L.as_bytes());
+            verifier.commit_bytes(b"R", R.as_bytes());
 
-            challenges.push(verifier.challenge_scalar());
+            challenges.push(verifier.challenge_scalar(b"x"));
         }
 
         let mut inv_challenges = challenges.clone();
@@ -160,22 +455,7 @@ impl Proof {
         }
         let challenges_sq = challenges;
 
-        // j-th bit of i
-        let bit = |i, j| 1 & (i >> j);
-
-        let mut s = Vec::with_capacity(n);
-        for i in 0..n {
-            let mut s_i = allinv;
-            // XXX remove this loop via the bit twiddling mentioned in the paper
-            for j in 0..lg_n {
-                if bit(i, j) == 1 {
-                    // The challenges are stored in "creation order" as [x_k,...,x_1]
-                    s_i *= challenges_sq[(lg_n - 1) - j];
-                }
-            }
-            s.push(s_i);
-        }
-        let s = s;
+        let s = Proof::build_s_vector(allinv, &challenges_sq, lg_n, n);
 
         let a_times_s = s.iter().map(|s_i| self.a * s_i);
 
@@ -183,25 +463,35 @@ impl Proof {
         let inv_s = s.iter().rev();
 
         let h_times_b_div_s = Hprime_factors
-            .into_iter()
+            .iter()
             .zip(inv_s)
-            .map(|(h_i, s_i_inv)| (self.b * s_i_inv) * h_i.borrow());
+            .map(|(h_i, s_i_inv)| (self.b * s_i_inv) * h_i);
 
         let neg_x_sq = challenges_sq.iter().map(|x| -x);
 
         let neg_x_inv_sq = inv_challenges.iter().map(|x_inv| -(x_inv * x_inv));
 
+        // A `Proof` only ever holds points that were compressed from a
+        // valid `RistrettoPoint` (by `create`) or checked to decompress
+        // in `from_bytes`, so these decompressions cannot fail.
+        let Ls = self.L_vec.iter().map(|p| p.decompress().unwrap());
+        let Rs = self.R_vec.iter().map(|p| p.decompress().unwrap());
+
+        let (H_blind, blind) = blind.unwrap_or((RistrettoPoint::identity(), Scalar::zero()));
+
         let expect_P = ristretto::vartime::multiscalar_mult(
             iter::once(self.a * self.b)
                 .chain(a_times_s)
                 .chain(h_times_b_div_s)
                 .chain(neg_x_sq)
-                .chain(neg_x_inv_sq),
-            iter::once(Q)
-                .chain(G_vec.iter())
-                .chain(H_vec.iter())
-                .chain(self.L_vec.iter())
-                .chain(self.R_vec.iter()),
+                .chain(neg_x_inv_sq)
+                .chain(iter::once(blind)),
+            iter::once(*Q)
+                .chain(G_vec.iter().cloned())
+                .chain(H_vec.iter().cloned())
+                .chain(Ls)
+                .chain(Rs)
+                .chain(iter::once(H_blind)),
         );
 
         if expect_P == *P {
@@ -210,6 +500,264 @@ impl Proof {
             Err(())
         }
     }
+
+    /// Deterministic extra generators used to pad `G_vec`/`H_vec` from
+    /// `n` up to `padded_n` (a power of two) in `create` and `verify`.
+    ///
+    /// These are independent of the caller's own generators (they're
+    /// derived from a fixed domain-separation label and the padding
+    /// index), so both sides of a proof reconstruct exactly the same
+    /// padding without having to agree on it out of band.
+    fn padding_generators(n: usize, padded_n: usize) -> (Vec<RistrettoPoint>, Vec<RistrettoPoint>) {
+        let G_pad = (n..padded_n)
+            .map(|i| RistrettoPoint::hash_from_bytes::<Sha256>(format!("ipp pad G {}", i).as_bytes()))
+            .collect();
+        let H_pad = (n..padded_n)
+            .map(|i| RistrettoPoint::hash_from_bytes::<Sha256>(format!("ipp pad H {}", i).as_bytes()))
+            .collect();
+        (G_pad, H_pad)
+    }
+
+    /// Computes `s[i] = allinv * prod_{j : bit j of i is 1} x_{j+1}^2`
+    /// (with `challenges_sq` in creation order `[x_k^2,...,x_1^2]`, so
+    /// `x_{j+1}^2` is `challenges_sq[(lg_n - 1) - j]`) for every `i` in
+    /// `0..n` in O(n), using the fact that `i` and `i - 2^(lg_i)` (where
+    /// `lg_i` is `i`'s highest set bit) differ only in bit `lg_i`, so
+    /// `s[i] = s[i - 2^(lg_i)] * x_{lg_i+1}^2`.
+    fn build_s_vector(allinv: Scalar, challenges_sq: &[Scalar], lg_n: usize, n: usize) -> Vec<Scalar> {
+        let mut s = Vec::with_capacity(n);
+        s.push(allinv);
+        for i in 1..n {
+            let lg_i = 31 - (i as u32).leading_zeros();
+            let k = 1 << lg_i;
+            // The challenges are stored in "creation order" as [x_k,...,x_1]
+            s.push(s[i - k] * challenges_sq[(lg_n - 1) - lg_i as usize]);
+        }
+        s
+    }
+
+    /// Verifies a batch of proofs that all share the same `G_vec` /
+    /// `H_vec` bases, checking them with a single combined
+    /// multiscalar multiplication instead of one per proof.
+    ///
+    /// Each proof `j` is weighted by an independent scalar `r_j`,
+    /// drawn from `j`'s own transcript after its round challenges
+    /// have been absorbed, so a batch containing one invalid proof
+    /// fails except with negligible probability. The weighted `a*s[i]`
+    /// and `b*s[!i]` contributions to the shared `G_vec`/`H_vec` bases
+    /// are summed across every proof in the batch before the single
+    /// multiscalar mult, which is what amortizes the dominant `2n`
+    /// base exponentiation over the whole batch.
+    ///
+    /// Unlike `verify`, this does not pad `G_vec`/`H_vec` (or
+    /// `Hprime_factors`) for non-power-of-two lengths: every proof in
+    /// the batch must have been created against the already-padded
+    /// `G_vec`/`H_vec` passed in here (so `G_vec.len()` must equal
+    /// `1 << lg_n` for each proof's `lg_n`), and each `Hprime_factors[j]`
+    /// must already have that same padded length, or this returns
+    /// `Err(())`. Callers coming from the `create`/`verify`
+    /// non-power-of-two padding path (see `Proof::create`) must pad
+    /// their true-length `Hprime_factors` out to `G_vec.len()` (e.g.
+    /// with `Scalar::from_u64(1)`, matching the padding `create`/`verify`
+    /// apply to `Hprime_factors`) before calling this.
+    pub fn verify_batch<T: ProofTranscriptProtocol>(
+        proofs: &[Proof],
+        transcripts: &mut [T],
+        Hprime_factors: &[Vec<Scalar>],
+        Ps: &[RistrettoPoint],
+        Q: &RistrettoPoint,
+        G_vec: &Vec<RistrettoPoint>,
+        H_vec: &Vec<RistrettoPoint>,
+    ) -> Result<(), ()> {
+        let m = proofs.len();
+        assert_eq!(transcripts.len(), m);
+        assert_eq!(Hprime_factors.len(), m);
+        assert_eq!(Ps.len(), m);
+
+        let n = G_vec.len();
+        assert_eq!(H_vec.len(), n);
+
+        let mut g_scalars = vec![Scalar::zero(); n];
+        let mut h_scalars = vec![Scalar::zero(); n];
+        let mut q_scalar = Scalar::zero();
+
+        let mut dynamic_scalars = Vec::new();
+        let mut dynamic_points = Vec::new();
+
+        for j in 0..m {
+            let proof = &proofs[j];
+            let lg_n = proof.L_vec.len();
+            if n != (1 << lg_n) {
+                return Err(());
+            }
+            if Hprime_factors[j].len() != n {
+                return Err(());
+            }
+
+            let mut challenges = Vec::with_capacity(lg_n);
+            for (L, R) in proof.L_vec.iter().zip(proof.R_vec.iter()) {
+                transcripts[j].commit_bytes(b"L", L.as_bytes());
+                transcripts[j].commit_bytes(b"R", R.as_bytes());
+                challenges.push(transcripts[j].challenge_scalar(b"x"));
+            }
+
+            // Bind this proof's batching weight to its own transcript,
+            // so it can't be chosen independently of the proof.
+            let r = transcripts[j].challenge_scalar(b"r");
+
+            let mut inv_challenges = challenges.clone();
+            let allinv = scalar::batch_invert(&mut inv_challenges);
+
+            for x in challenges.iter_mut() {
+                *x = &*x * &*x;
+            }
+            let challenges_sq = challenges;
+
+            let s = Proof::build_s_vector(allinv, &challenges_sq, lg_n, n);
+
+            for i in 0..n {
+                g_scalars[i] = g_scalars[i] + r * proof.a * s[i];
+                h_scalars[i] = h_scalars[i] + r * proof.b * s[n - 1 - i] * Hprime_factors[j][i];
+            }
+
+            q_scalar = q_scalar + r * proof.a * proof.b;
+
+            for (x_sq, L) in challenges_sq.iter().zip(proof.L_vec.iter()) {
+                dynamic_scalars.push(-r * x_sq);
+                dynamic_points.push(L.decompress().ok_or(())?);
+            }
+            for (xinv, R) in inv_challenges.iter().zip(proof.R_vec.iter()) {
+                dynamic_scalars.push(-(r * xinv * xinv));
+                dynamic_points.push(R.decompress().ok_or(())?);
+            }
+
+            dynamic_scalars.push(-r);
+            dynamic_points.push(Ps[j]);
+        }
+
+        let scalars = iter::once(q_scalar)
+            .chain(g_scalars)
+            .chain(h_scalars)
+            .chain(dynamic_scalars);
+
+        let points = iter::once(*Q)
+            .chain(G_vec.iter().cloned())
+            .chain(H_vec.iter().cloned())
+            .chain(dynamic_points);
+
+        let mega_check = ristretto::vartime::multiscalar_mult(scalars, points);
+
+        if mega_check == RistrettoPoint::identity() {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Serializes the proof into a byte array of the canonical format
+    /// used by the Solana and Spartan forks of this protocol: the
+    /// `lg_n` pairs of compressed `L`/`R` points (32 bytes each),
+    /// followed by the two scalars `a` and `b` (32 bytes each).
+    ///
+    /// A proof for a vector of length `n = 2^k` is therefore exactly
+    /// `(2k + 2) * 32` bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let lg_n = self.L_vec.len();
+        let mut buf = Vec::with_capacity((2 * lg_n + 2) * 32);
+        for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            buf.extend_from_slice(L.as_bytes());
+            buf.extend_from_slice(R.as_bytes());
+        }
+        buf.extend_from_slice(self.a.as_bytes());
+        buf.extend_from_slice(self.b.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice produced by
+    /// `to_bytes`.
+    ///
+    /// Recovers `lg_n` from the slice length, rejecting any length
+    /// that is not of the form `(2k + 2) * 32`, checks that every
+    /// 32-byte chunk for `L`/`R` decompresses to a valid Ristretto
+    /// point, and rejects non-canonical encodings of `a`/`b` (i.e.
+    /// byte strings that represent an integer `>= l`), so that a
+    /// proof round-trips through `to_bytes`/`from_bytes` to a unique
+    /// byte string and can't be mutated into an equivalent-but-
+    /// distinct encoding.
+    pub fn from_bytes(slice: &[u8]) -> Result<Proof, ()> {
+        if slice.len() % 32 != 0 {
+            return Err(());
+        }
+        let num_elements = slice.len() / 32;
+        if num_elements < 2 || (num_elements - 2) % 2 != 0 {
+            return Err(());
+        }
+        let lg_n = (num_elements - 2) / 2;
+
+        let mut L_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        for i in 0..lg_n {
+            let L = CompressedRistretto::from_slice(&slice[(2 * i) * 32..(2 * i + 1) * 32]);
+            let R = CompressedRistretto::from_slice(&slice[(2 * i + 1) * 32..(2 * i + 2) * 32]);
+            // Reject points that don't decompress, but keep only the
+            // compressed form: that's what's committed to the
+            // transcript and what we re-serialize in `to_bytes`.
+            L.decompress().ok_or(())?;
+            R.decompress().ok_or(())?;
+            L_vec.push(L);
+            R_vec.push(R);
+        }
+
+        let pos = 2 * lg_n * 32;
+        let mut a_bytes = [0u8; 32];
+        a_bytes.copy_from_slice(&slice[pos..pos + 32]);
+        let mut b_bytes = [0u8; 32];
+        b_bytes.copy_from_slice(&slice[pos + 32..pos + 64]);
+
+        Ok(Proof {
+            L_vec: L_vec,
+            R_vec: R_vec,
+            a: Scalar::from_canonical_bytes(a_bytes).ok_or(())?,
+            b: Scalar::from_canonical_bytes(b_bytes).ok_or(())?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Proof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Proof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        struct ProofVisitor;
+
+        impl<'de> ::serde::de::Visitor<'de> for ProofVisitor {
+            type Value = Proof;
+
+            fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                formatter.write_str("a valid Proof of the canonical (2*lg_n + 2) * 32 byte length")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Proof, E>
+            where
+                E: ::serde::de::Error,
+            {
+                Proof::from_bytes(v).map_err(|_| E::custom("deserialization error"))
+            }
+        }
+
+        deserializer.deserialize_bytes(ProofVisitor)
+    }
 }
 
 #[cfg(test)]
@@ -295,6 +843,531 @@ mod tests {
     fn make_ipp_64() {
         test_helper_create(64);
     }
+
+    #[test]
+    fn make_ipp_3() {
+        test_helper_create(3);
+    }
+
+    #[test]
+    fn make_ipp_5() {
+        test_helper_create(5);
+    }
+
+    #[test]
+    fn make_ipp_6() {
+        test_helper_create(6);
+    }
+
+    #[test]
+    fn make_ipp_100() {
+        test_helper_create(100);
+    }
+
+    #[test]
+    fn proof_to_from_bytes_roundtrip() {
+        let mut rng = OsRng::new().unwrap();
+        let n = 4;
+
+        let B = &RistrettoPoint::hash_from_bytes::<Sha256>("hello".as_bytes());
+        let B_blinding = &RistrettoPoint::hash_from_bytes::<Sha256>("there".as_bytes());
+        let G = make_generators(B, n);
+        let H = make_generators(B_blinding, n);
+        let Q = RistrettoPoint::hash_from_bytes::<Sha256>(b"test point");
+
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+
+        let mut verifier = ProofTranscript::new(b"innerproducttest");
+        let proof = Proof::create(
+            &mut verifier,
+            &Q,
+            util::exp_iter(Scalar::one()),
+            G.clone(),
+            H.clone(),
+            a.clone(),
+            b.clone(),
+        );
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), (2 * 2 /* lg_n for n=4 */ + 2) * 32);
+
+        let decoded = Proof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn proof_from_bytes_rejects_bad_length() {
+        // Not a multiple of 32.
+        assert!(Proof::from_bytes(&[0u8; 31]).is_err());
+        // A multiple of 32, but not of the form (2k + 2) * 32.
+        assert!(Proof::from_bytes(&[0u8; 32]).is_err());
+        assert!(Proof::from_bytes(&[0u8; 3 * 32]).is_err());
+    }
+
+    #[test]
+    fn proof_from_bytes_rejects_non_decompressible_point() {
+        // lg_n = 1, so 4 * 32 = 128 bytes; fill L with bytes that are
+        // not the encoding of any Ristretto point.
+        let mut bytes = vec![0u8; 4 * 32];
+        for b in bytes[0..32].iter_mut() {
+            *b = 0xff;
+        }
+        assert!(Proof::from_bytes(&bytes).is_err());
+    }
+
+    // The little-endian encoding of `l` (the group order): it reduces
+    // to zero mod `l`, but is not a canonical encoding, so
+    // `from_canonical_bytes` must reject it.
+    const NON_CANONICAL_L: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde,
+        0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x10,
+    ];
+
+    // Builds a valid proof's bytes, then overwrites the 32-byte scalar
+    // chunk `chunks_from_end` chunks before the end (1 = `b`, 2 = `a`)
+    // with `NON_CANONICAL_L`, for `proof_from_bytes_rejects_non_canonical_scalar_{a,b}`.
+    fn proof_bytes_with_non_canonical_scalar(chunks_from_end: usize) -> Vec<u8> {
+        let n = 4;
+        let B = &RistrettoPoint::hash_from_bytes::<Sha256>("hello".as_bytes());
+        let B_blinding = &RistrettoPoint::hash_from_bytes::<Sha256>("there".as_bytes());
+        let G = make_generators(B, n);
+        let H = make_generators(B_blinding, n);
+        let Q = RistrettoPoint::hash_from_bytes::<Sha256>(b"test point");
+
+        let mut rng = OsRng::new().unwrap();
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+
+        let mut verifier = ProofTranscript::new(b"innerproducttest");
+        let proof = Proof::create(
+            &mut verifier,
+            &Q,
+            util::exp_iter(Scalar::one()),
+            G,
+            H,
+            a,
+            b,
+        );
+
+        let mut bytes = proof.to_bytes();
+        let len = bytes.len();
+        let start = len - chunks_from_end * 32;
+        bytes[start..start + 32].copy_from_slice(&NON_CANONICAL_L);
+        bytes
+    }
+
+    #[test]
+    fn proof_from_bytes_rejects_non_canonical_scalar_a() {
+        let bytes = proof_bytes_with_non_canonical_scalar(2);
+        assert!(Proof::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn proof_from_bytes_rejects_non_canonical_scalar_b() {
+        let bytes = proof_bytes_with_non_canonical_scalar(1);
+        assert!(Proof::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn build_s_vector_matches_naive_bit_loop() {
+        let mut rng = OsRng::new().unwrap();
+        let lg_n = 5;
+        let n = 1 << lg_n;
+
+        // Challenges are stored in "creation order" as [x_{lg_n},...,x_1].
+        let challenges: Vec<_> = (0..lg_n).map(|_| Scalar::random(&mut rng)).collect();
+        let challenges_sq: Vec<_> = challenges.iter().map(|x| x * x).collect();
+        let allinv = challenges
+            .iter()
+            .fold(Scalar::one(), |acc, x| acc * x.invert());
+
+        let fast = Proof::build_s_vector(allinv, &challenges_sq, lg_n, n);
+
+        // Naive reference: s[i] = allinv * prod_{j : bit j of i is 1} x_{j+1}^2,
+        // where bit j corresponds to challenges_sq[(lg_n - 1) - j].
+        let naive: Vec<_> = (0..n)
+            .map(|i| {
+                let mut s_i = allinv;
+                for j in 0..lg_n {
+                    if (i >> j) & 1 == 1 {
+                        s_i = s_i * challenges_sq[(lg_n - 1) - j];
+                    }
+                }
+                s_i
+            })
+            .collect();
+
+        assert_eq!(fast, naive);
+    }
+
+    // Fixed, per-index domain-separation labels for the batch tests
+    // below: each proof in a batch needs its own transcript, so its
+    // batching weight `r` (see `verify_batch`) is bound to that proof
+    // alone.
+    const BATCH_LABELS: [&'static [u8]; 4] = [b"batchtest0", b"batchtest1", b"batchtest2", b"batchtest3"];
+
+    fn test_helper_batch(n: usize, m: usize) -> (Vec<Proof>, Vec<Vec<Scalar>>, Vec<RistrettoPoint>, RistrettoPoint, Vec<RistrettoPoint>, Vec<RistrettoPoint>) {
+        let mut rng = OsRng::new().unwrap();
+
+        let B = &RistrettoPoint::hash_from_bytes::<Sha256>("hello".as_bytes());
+        let B_blinding = &RistrettoPoint::hash_from_bytes::<Sha256>("there".as_bytes());
+        let G = make_generators(B, n);
+        let H = make_generators(B_blinding, n);
+        let Q = RistrettoPoint::hash_from_bytes::<Sha256>(b"test point");
+
+        let mut proofs = Vec::with_capacity(m);
+        let mut Hprime_factors = Vec::with_capacity(m);
+        let mut Ps = Vec::with_capacity(m);
+
+        for j in 0..m {
+            let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let c = inner_product(&a, &b);
+
+            let y_inv = Scalar::random(&mut rng);
+            let b_prime = b.iter().zip(util::exp_iter(y_inv)).map(|(bi, yi)| bi * yi);
+            let a_prime = a.iter().cloned();
+
+            let P = ristretto::vartime::multiscalar_mult(
+                a_prime.chain(b_prime).chain(iter::once(c)),
+                G.iter().chain(H.iter()).chain(iter::once(&Q)),
+            );
+
+            let mut prover = ProofTranscript::new(BATCH_LABELS[j]);
+            let proof = Proof::create(
+                &mut prover,
+                &Q,
+                util::exp_iter(y_inv),
+                G.clone(),
+                H.clone(),
+                a,
+                b,
+            );
+
+            proofs.push(proof);
+            Hprime_factors.push(util::exp_iter(y_inv).take(n).collect());
+            Ps.push(P);
+        }
+
+        (proofs, Hprime_factors, Ps, Q, G, H)
+    }
+
+    #[test]
+    fn verify_batch_accepts_valid_batch() {
+        let (proofs, Hprime_factors, Ps, Q, G, H) = test_helper_batch(16, 4);
+
+        let mut transcripts: Vec<_> = (0..proofs.len())
+            .map(|j| ProofTranscript::new(BATCH_LABELS[j]))
+            .collect();
+
+        assert!(
+            Proof::verify_batch(&proofs, &mut transcripts, &Hprime_factors, &Ps, &Q, &G, &H)
+                .is_ok()
+        );
+
+        // Cross-check against individual `verify` of each proof.
+        for j in 0..proofs.len() {
+            let mut verifier = ProofTranscript::new(BATCH_LABELS[j]);
+            assert!(
+                proofs[j]
+                    .verify(
+                        &mut verifier,
+                        Hprime_factors[j].clone(),
+                        &Ps[j],
+                        &Q,
+                        &G,
+                        &H
+                    )
+                    .is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn verify_batch_rejects_tampered_proof() {
+        let (mut proofs, Hprime_factors, Ps, Q, G, H) = test_helper_batch(16, 4);
+
+        // Tamper with one proof's `a` scalar.
+        let mut rng = OsRng::new().unwrap();
+        proofs[1].a = Scalar::random(&mut rng);
+
+        let mut transcripts: Vec<_> = (0..proofs.len())
+            .map(|j| ProofTranscript::new(BATCH_LABELS[j]))
+            .collect();
+
+        assert!(
+            Proof::verify_batch(&proofs, &mut transcripts, &Hprime_factors, &Ps, &Q, &G, &H)
+                .is_err()
+        );
+    }
+
+    // Like `test_helper_batch`, but `n` is a non-power-of-two true
+    // length: `G_vec`/`H_vec` (and the returned `Hprime_factors`) come
+    // back already padded to `n.next_power_of_two()`, as `verify_batch`
+    // requires.
+    fn test_helper_batch_padded(n: usize, m: usize) -> (Vec<Proof>, Vec<Vec<Scalar>>, Vec<RistrettoPoint>, RistrettoPoint, Vec<RistrettoPoint>, Vec<RistrettoPoint>) {
+        let mut rng = OsRng::new().unwrap();
+        let padded_n = n.next_power_of_two();
+
+        let B = &RistrettoPoint::hash_from_bytes::<Sha256>("hello".as_bytes());
+        let B_blinding = &RistrettoPoint::hash_from_bytes::<Sha256>("there".as_bytes());
+        let G = make_generators(B, n);
+        let H = make_generators(B_blinding, n);
+        let Q = RistrettoPoint::hash_from_bytes::<Sha256>(b"test point");
+
+        let (G_pad, H_pad) = Proof::padding_generators(n, padded_n);
+        let mut G_padded = G.clone();
+        G_padded.extend(G_pad);
+        let mut H_padded = H.clone();
+        H_padded.extend(H_pad);
+
+        let mut proofs = Vec::with_capacity(m);
+        let mut Hprime_factors = Vec::with_capacity(m);
+        let mut Ps = Vec::with_capacity(m);
+
+        for j in 0..m {
+            let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let c = inner_product(&a, &b);
+
+            let y_inv = Scalar::random(&mut rng);
+            let b_prime = b.iter().zip(util::exp_iter(y_inv)).map(|(bi, yi)| bi * yi);
+            let a_prime = a.iter().cloned();
+
+            let P = ristretto::vartime::multiscalar_mult(
+                a_prime.chain(b_prime).chain(iter::once(c)),
+                G.iter().chain(H.iter()).chain(iter::once(&Q)),
+            );
+
+            let mut prover = ProofTranscript::new(BATCH_LABELS[j]);
+            let proof = Proof::create(
+                &mut prover,
+                &Q,
+                util::exp_iter(y_inv),
+                G.clone(),
+                H.clone(),
+                a,
+                b,
+            );
+
+            proofs.push(proof);
+            let mut hprime: Vec<Scalar> = util::exp_iter(y_inv).take(n).collect();
+            hprime.resize(padded_n, Scalar::from_u64(1));
+            Hprime_factors.push(hprime);
+            Ps.push(P);
+        }
+
+        (proofs, Hprime_factors, Ps, Q, G_padded, H_padded)
+    }
+
+    #[test]
+    fn verify_batch_accepts_non_power_of_two_batch_with_padded_hprime_factors() {
+        // n = 5 is not a power of two; `G_vec`/`H_vec`/`Hprime_factors`
+        // are all padded to 8 here, as `verify_batch`'s docstring
+        // requires of callers coming from the `create`/`verify`
+        // non-power-of-two padding path.
+        let (proofs, Hprime_factors, Ps, Q, G, H) = test_helper_batch_padded(5, 4);
+
+        let mut transcripts: Vec<_> = (0..proofs.len())
+            .map(|j| ProofTranscript::new(BATCH_LABELS[j]))
+            .collect();
+
+        assert!(
+            Proof::verify_batch(&proofs, &mut transcripts, &Hprime_factors, &Ps, &Q, &G, &H)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn verify_batch_rejects_unpadded_hprime_factors() {
+        // A caller who only pads `G_vec`/`H_vec` (as the docstring
+        // demands) but passes the true-length `Hprime_factors` that
+        // `verify` itself would accept and auto-pad must get `Err(())`
+        // back, not a panic.
+        let n = 5;
+        let (proofs, padded_factors, Ps, Q, G, H) = test_helper_batch_padded(n, 4);
+        let unpadded_factors: Vec<Vec<Scalar>> = padded_factors
+            .iter()
+            .map(|factors| factors[..n].to_vec())
+            .collect();
+
+        let mut transcripts: Vec<_> = (0..proofs.len())
+            .map(|j| ProofTranscript::new(BATCH_LABELS[j]))
+            .collect();
+
+        assert!(
+            Proof::verify_batch(&proofs, &mut transcripts, &unpadded_factors, &Ps, &Q, &G, &H)
+                .is_err()
+        );
+    }
+
+    fn test_helper_create_blinded(n: usize) -> (Proof, Scalar, RistrettoPoint, RistrettoPoint, RistrettoPoint, Vec<RistrettoPoint>, Vec<RistrettoPoint>) {
+        let mut rng = OsRng::new().unwrap();
+        let lg_n = n.next_power_of_two().trailing_zeros() as usize;
+
+        let B = &RistrettoPoint::hash_from_bytes::<Sha256>("hello".as_bytes());
+        let B_blinding = &RistrettoPoint::hash_from_bytes::<Sha256>("there".as_bytes());
+        let G = make_generators(B, n);
+        let H = make_generators(B_blinding, n);
+        let H_blind = RistrettoPoint::hash_from_bytes::<Sha256>(b"blinding base");
+        let Q = RistrettoPoint::hash_from_bytes::<Sha256>(b"test point");
+
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let c = inner_product(&a, &b);
+
+        let initial_blind = Scalar::random(&mut rng);
+        let l_blinds: Vec<_> = (0..lg_n).map(|_| Scalar::random(&mut rng)).collect();
+        let r_blinds: Vec<_> = (0..lg_n).map(|_| Scalar::random(&mut rng)).collect();
+
+        let P = ristretto::vartime::multiscalar_mult(
+            a.iter()
+                .cloned()
+                .chain(b.iter().cloned())
+                .chain(iter::once(c))
+                .chain(iter::once(initial_blind)),
+            G.iter().chain(H.iter()).chain(iter::once(&Q)).chain(iter::once(&H_blind)),
+        );
+
+        let mut prover = ProofTranscript::new(b"blindedippttest");
+        let (proof, blind) = Proof::create_blinded(
+            &mut prover,
+            &Q,
+            &H_blind,
+            iter::repeat(Scalar::one()),
+            G.clone(),
+            H.clone(),
+            a,
+            b,
+            initial_blind,
+            l_blinds.into_iter(),
+            r_blinds.into_iter(),
+        );
+
+        (proof, blind, P, Q, H_blind, G, H)
+    }
+
+    #[test]
+    fn create_blinded_verify_blinded_roundtrip() {
+        let (proof, blind, P, Q, H_blind, G, H) = test_helper_create_blinded(4);
+
+        let mut verifier = ProofTranscript::new(b"blindedippttest");
+        assert!(
+            proof
+                .verify_blinded(
+                    &mut verifier,
+                    iter::repeat(Scalar::one()),
+                    &P,
+                    &Q,
+                    &H_blind,
+                    &blind,
+                    &G,
+                    &H
+                )
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn verify_blinded_rejects_wrong_blind() {
+        let (proof, blind, P, Q, H_blind, G, H) = test_helper_create_blinded(4);
+
+        let mut rng = OsRng::new().unwrap();
+        let wrong_blind = blind + Scalar::random(&mut rng);
+
+        let mut verifier = ProofTranscript::new(b"blindedippttest");
+        assert!(
+            proof
+                .verify_blinded(
+                    &mut verifier,
+                    iter::repeat(Scalar::one()),
+                    &P,
+                    &Q,
+                    &H_blind,
+                    &wrong_blind,
+                    &G,
+                    &H
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn merlin_transcript_is_deterministic() {
+        let mut a = MerlinTranscript::new(b"merlindeterminism");
+        let mut b = MerlinTranscript::new(b"merlindeterminism");
+
+        a.commit_bytes(b"L", b"some L point");
+        b.commit_bytes(b"L", b"some L point");
+        a.commit_bytes(b"R", b"some R point");
+        b.commit_bytes(b"R", b"some R point");
+
+        assert_eq!(
+            a.challenge_scalar(b"x").to_bytes(),
+            b.challenge_scalar(b"x").to_bytes()
+        );
+    }
+
+    #[test]
+    fn merlin_transcript_ipp_roundtrip() {
+        let mut rng = OsRng::new().unwrap();
+        let n = 8;
+
+        let B = &RistrettoPoint::hash_from_bytes::<Sha256>("hello".as_bytes());
+        let B_blinding = &RistrettoPoint::hash_from_bytes::<Sha256>("there".as_bytes());
+        let G = make_generators(B, n);
+        let H = make_generators(B_blinding, n);
+        let Q = RistrettoPoint::hash_from_bytes::<Sha256>(b"test point");
+
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let c = inner_product(&a, &b);
+
+        let P = ristretto::vartime::multiscalar_mult(
+            a.iter().cloned().chain(b.iter().cloned()).chain(iter::once(c)),
+            G.iter().chain(H.iter()).chain(iter::once(&Q)),
+        );
+
+        let mut prover = MerlinTranscript::new(b"merlinippttest");
+        let proof = Proof::create(
+            &mut prover,
+            &Q,
+            iter::repeat(Scalar::one()),
+            G.clone(),
+            H.clone(),
+            a.clone(),
+            b.clone(),
+        );
+
+        let mut verifier = MerlinTranscript::new(b"merlinippttest");
+        assert!(
+            proof
+                .verify(&mut verifier, iter::repeat(Scalar::one()), &P, &Q, &G, &H)
+                .is_ok()
+        );
+
+        // A proof created under the legacy `ProofTranscript` commits to
+        // a different challenge sequence, so it must not verify under
+        // `MerlinTranscript` with the same label.
+        let mut legacy_prover = ProofTranscript::new(b"merlinippttest");
+        let legacy_proof = Proof::create(
+            &mut legacy_prover,
+            &Q,
+            iter::repeat(Scalar::one()),
+            G.clone(),
+            H.clone(),
+            a.clone(),
+            b.clone(),
+        );
+        let mut merlin_verifier = MerlinTranscript::new(b"merlinippttest");
+        assert!(
+            legacy_proof
+                .verify(&mut merlin_verifier, iter::repeat(Scalar::one()), &P, &Q, &G, &H)
+                .is_err()
+        );
+    }
 }
 
 #[cfg(test)]